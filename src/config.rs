@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use directories::ProjectDirs;
+use log::{info, warn};
+
+/// Whether to use the synthetic `DevModeSource` instead of the real HWiNFO
+/// shared-memory reader. Defaults to off on Windows and to on everywhere
+/// else, where the real reader cannot run at all.
+#[cfg(windows)]
+const DEFAULT_DEV_MODE: bool = false;
+#[cfg(not(windows))]
+const DEFAULT_DEV_MODE: bool = true;
+
+/// Runtime settings loaded from the user's config directory, with defaults
+/// used for anything missing or unparsable.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind_address: String,
+    pub port: u16,
+    pub log_level: log::LevelFilter,
+    pub retry_attempts: u32,
+    pub retry_interval: Duration,
+    pub dev_mode: bool,
+    pub extra_stats: bool,
+    pub stable_ids: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind_address: "127.0.0.1".to_string(),
+            port: 8080,
+            log_level: log::LevelFilter::Debug,
+            retry_attempts: 5,
+            retry_interval: Duration::from_secs(5),
+            dev_mode: DEFAULT_DEV_MODE,
+            extra_stats: false,
+            stable_ids: false,
+        }
+    }
+}
+
+impl Config {
+    /// Reads `config.toml` from `dirs`'s config directory, falling back to
+    /// `Config::default()` if the file is absent or empty.
+    pub fn load(dirs: &ProjectDirs) -> Self {
+        let path = dirs.config_dir().join("config.toml");
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(_) => {
+                info!("No config file found at {}, using defaults", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    fn parse(contents: &str) -> Self {
+        let mut config = Self::default();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("Ignoring malformed config line: {}", line);
+                continue;
+            };
+            let key = key.trim();
+            let value = value.trim().trim_matches('"');
+            match key {
+                "bind_address" => config.bind_address = value.to_string(),
+                "port" => match value.parse() {
+                    Ok(port) => config.port = port,
+                    Err(_) => warn!("Invalid port value: {}", value),
+                },
+                "log_level" => match value.parse() {
+                    Ok(level) => config.log_level = level,
+                    Err(_) => warn!("Invalid log_level value: {}", value),
+                },
+                "retry_attempts" => match value.parse() {
+                    Ok(attempts) => config.retry_attempts = attempts,
+                    Err(_) => warn!("Invalid retry_attempts value: {}", value),
+                },
+                "retry_interval" => match value.parse::<u64>() {
+                    Ok(secs) => config.retry_interval = Duration::from_secs(secs),
+                    Err(_) => warn!("Invalid retry_interval value: {}", value),
+                },
+                "dev_mode" => match value.parse() {
+                    Ok(dev_mode) => config.dev_mode = dev_mode,
+                    Err(_) => warn!("Invalid dev_mode value: {}", value),
+                },
+                "extra_stats" => match value.parse() {
+                    Ok(extra_stats) => config.extra_stats = extra_stats,
+                    Err(_) => warn!("Invalid extra_stats value: {}", value),
+                },
+                "stable_ids" => match value.parse() {
+                    Ok(stable_ids) => config.stable_ids = stable_ids,
+                    Err(_) => warn!("Invalid stable_ids value: {}", value),
+                },
+                _ => warn!("Unknown config key: {}", key),
+            }
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_values_for_every_field() {
+        let config = Config::parse(
+            "bind_address = \"0.0.0.0\"\n\
+             port = 9000\n\
+             log_level = \"warn\"\n\
+             retry_attempts = 10\n\
+             retry_interval = 30\n\
+             dev_mode = true\n\
+             extra_stats = true\n\
+             stable_ids = true\n",
+        );
+
+        assert_eq!(config.bind_address, "0.0.0.0");
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.log_level, log::LevelFilter::Warn);
+        assert_eq!(config.retry_attempts, 10);
+        assert_eq!(config.retry_interval, Duration::from_secs(30));
+        assert!(config.dev_mode);
+        assert!(config.extra_stats);
+        assert!(config.stable_ids);
+    }
+
+    #[test]
+    fn ignores_blank_lines_and_comments() {
+        let config = Config::parse("\n# a comment\n   \nport = 9000\n");
+        assert_eq!(config.port, 9000);
+    }
+
+    #[test]
+    fn ignores_malformed_lines_without_an_equals_sign() {
+        let config = Config::parse("this is not a valid line\nport = 9000\n");
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.bind_address, Config::default().bind_address);
+    }
+
+    #[test]
+    fn ignores_unknown_keys() {
+        let config = Config::parse("made_up_key = 1\nport = 9000\n");
+        assert_eq!(config.port, 9000);
+    }
+
+    #[test]
+    fn falls_back_to_the_default_when_a_value_fails_to_parse() {
+        let defaults = Config::default();
+        let config = Config::parse(
+            "port = not_a_number\n\
+             log_level = not_a_level\n\
+             retry_attempts = not_a_number\n\
+             retry_interval = not_a_number\n\
+             dev_mode = not_a_bool\n\
+             extra_stats = not_a_bool\n\
+             stable_ids = not_a_bool\n",
+        );
+
+        assert_eq!(config.port, defaults.port);
+        assert_eq!(config.log_level, defaults.log_level);
+        assert_eq!(config.retry_attempts, defaults.retry_attempts);
+        assert_eq!(config.retry_interval, defaults.retry_interval);
+        assert_eq!(config.dev_mode, defaults.dev_mode);
+        assert_eq!(config.extra_stats, defaults.extra_stats);
+        assert_eq!(config.stable_ids, defaults.stable_ids);
+    }
+}
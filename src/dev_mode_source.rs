@@ -0,0 +1,140 @@
+use crate::hwinfo_types::{HWiNFOReadingElement, HWiNFOSensorElement, SensorReadingType};
+use crate::sensor_source::{SensorSource, SourceError};
+
+/// Polling period reported to callers; HWiNFO itself commonly defaults to 2s.
+const SYNTHETIC_POLLING_PERIOD_MS: u32 = 2000;
+
+const READING_TYPES: [SensorReadingType; 8] = [
+    SensorReadingType::Temp,
+    SensorReadingType::Volt,
+    SensorReadingType::Fan,
+    SensorReadingType::Current,
+    SensorReadingType::Power,
+    SensorReadingType::Clock,
+    SensorReadingType::Usage,
+    SensorReadingType::Other,
+];
+
+/// Synthetic `SensorSource` that emits one sensor with a reading of every
+/// `SensorReadingType` except `None`, so the metrics/registry pipeline is exercisable
+/// without HWiNFO running or Windows present.
+pub struct DevModeSource {
+    sensors: Vec<HWiNFOSensorElement>,
+    readings: Vec<HWiNFOReadingElement>,
+    tick: u32,
+}
+
+impl DevModeSource {
+    pub fn new() -> Self {
+        let sensors = vec![HWiNFOSensorElement {
+            sensor_id: 1,
+            sensor_instance: 0,
+            original_name: [0; 128],
+            user_name: [0; 128],
+            user_name_utf8: str_to_array("Synthetic Sensor"),
+        }];
+
+        let readings = READING_TYPES.iter().enumerate().map(|(index, reading_type)| {
+            HWiNFOReadingElement {
+                reading_type: *reading_type,
+                sensor_index: 0,
+                reading_id: index as u32,
+                original_label: [0; 128],
+                user_label: [0; 128],
+                unit: [0; 16],
+                value: 0.0,
+                // Seeded from outside the synthetic [0, 99] range so the
+                // first `update()` call's min()/max() actually adopt the
+                // real sampled value instead of staying pinned at 0.0.
+                min_value: f64::MAX,
+                max_value: f64::MIN,
+                avg_value: 0.0,
+                user_label_utf8: str_to_array(&format!("{} reading", reading_type)),
+                unit_utf8: str_to_array(unit_for(*reading_type)),
+            }
+        }).collect();
+
+        Self { sensors, readings, tick: 0 }
+    }
+}
+
+impl SensorSource for DevModeSource {
+    fn sensors(&self) -> &[HWiNFOSensorElement] {
+        &self.sensors
+    }
+
+    fn readings(&self) -> &[HWiNFOReadingElement] {
+        &self.readings
+    }
+
+    fn polling_period(&self) -> u32 {
+        SYNTHETIC_POLLING_PERIOD_MS
+    }
+
+    fn update(&mut self) -> Result<(), SourceError> {
+        self.tick = self.tick.wrapping_add(1);
+        let value = (self.tick % 100) as f64;
+        for reading in &mut self.readings {
+            reading.value = value;
+            reading.min_value = reading.min_value.min(value);
+            reading.max_value = reading.max_value.max(value);
+            reading.avg_value = (reading.avg_value + value) / 2.0;
+        }
+        Ok(())
+    }
+}
+
+fn str_to_array<const N: usize>(s: &str) -> [u8; N] {
+    let mut buf = [0u8; N];
+    let bytes = s.as_bytes();
+    let len = bytes.len().min(N - 1);
+    buf[..len].copy_from_slice(&bytes[..len]);
+    buf
+}
+
+fn unit_for(reading_type: SensorReadingType) -> &'static str {
+    match reading_type {
+        SensorReadingType::Temp => "C",
+        SensorReadingType::Volt => "V",
+        SensorReadingType::Fan => "RPM",
+        SensorReadingType::Current => "A",
+        SensorReadingType::Power => "W",
+        SensorReadingType::Clock => "MHz",
+        SensorReadingType::Usage => "%",
+        SensorReadingType::None | SensorReadingType::Other => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emits_one_reading_per_type() {
+        let source = DevModeSource::new();
+        assert_eq!(source.readings().len(), READING_TYPES.len());
+        for reading_type in READING_TYPES {
+            let count = source.readings().iter()
+                .filter(|reading| reading.reading_type == reading_type)
+                .count();
+            assert_eq!(count, 1, "expected exactly one {:?} reading", reading_type);
+        }
+    }
+
+    #[test]
+    fn min_and_max_track_the_sampled_value_instead_of_sticking_to_zero() {
+        let mut source = DevModeSource::new();
+        source.update().unwrap();
+
+        for reading in source.readings() {
+            assert_eq!(reading.min_value, reading.value);
+            assert_eq!(reading.max_value, reading.value);
+        }
+
+        source.update().unwrap();
+        for reading in source.readings() {
+            assert!(reading.min_value <= reading.value);
+            assert!(reading.max_value >= reading.value);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::fmt::{Display, Formatter};
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[allow(unused)]
 pub enum SensorReadingType {
     None,
@@ -27,8 +27,15 @@ pub struct HWiNFOReadingElement {
     pub reading_type: SensorReadingType,
     pub sensor_index: u32,
     pub reading_id: u32,
+    // Raw (non-UTF8) mirrors of HWiNFO's shared-memory layout. Nothing reads
+    // them directly — the `_utf8` fields below are what callers use — but
+    // they must stay in place to keep this struct's layout matching the
+    // real shared-memory segment.
+    #[allow(dead_code)]
     pub original_label: [i8;128],
+    #[allow(dead_code)]
     pub user_label: [i8;128],
+    #[allow(dead_code)]
     pub unit: [i8;16],
     pub value: f64,
     pub min_value: f64,
@@ -43,7 +50,11 @@ pub struct HWiNFOReadingElement {
 pub struct HWiNFOSensorElement {
     pub sensor_id: u32,
     pub sensor_instance: u32,
+    // Raw (non-UTF8) mirror of HWiNFO's shared-memory layout; see the note
+    // on `HWiNFOReadingElement::original_label`.
+    #[allow(dead_code)]
     pub original_name: [i8;128],
+    #[allow(dead_code)]
     pub user_name: [i8;128],
     pub user_name_utf8: [u8;128],
 }
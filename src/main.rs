@@ -3,12 +3,15 @@
     windows_subsystem = "windows"
 )]
 
+use std::io;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU64;
 use std::time::{Duration, SystemTime};
 
 use axum::{Extension, Router};
 use axum::routing::get;
+use axum::serve::Listener;
 use directories::ProjectDirs;
 use log::{error, info, warn};
 use prometheus_client::encoding::EncodeLabelSet;
@@ -17,27 +20,88 @@ use prometheus_client::metrics::family::Family;
 use prometheus_client::metrics::gauge::Gauge;
 use prometheus_client::registry::Registry;
 use tokio::runtime::Handle;
-use tokio::sync::{mpsc, RwLock};
-use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::sync::RwLock;
 
+use crate::config::Config;
+use crate::dev_mode_source::DevModeSource;
+#[cfg(windows)]
 use crate::hwinfo_reader::Reader;
-use crate::hwinfo_types::SensorReadingType;
+use crate::hwinfo_types::{HWiNFOReadingElement, HWiNFOSensorElement, SensorReadingType};
+use crate::sensor_source::{SensorSource, SourceError};
 
+mod config;
+mod dev_mode_source;
 mod hwinfo_types;
+#[cfg(windows)]
 mod hwinfo_reader;
+mod sensor_source;
+#[cfg(windows)]
 mod table_types;
 
 fn utf8_to_str(utf8: &[u8]) -> String {
     std::ffi::CStr::from_bytes_until_nul(utf8).unwrap().to_str().unwrap().to_string()
 }
 
-struct AppState {
-    registry: Registry,
-    tx_rq: Sender<()>,
-    rx_rs: Receiver<()>,
+/// Picks the real HWiNFO reader or the synthetic dev-mode backend per
+/// `dev_mode`. Off Windows the real reader does not exist at all, so the
+/// synthetic backend is used unconditionally regardless of the flag.
+#[cfg_attr(not(windows), allow(unused_variables))]
+fn create_source(dev_mode: bool) -> Result<Box<dyn SensorSource>, SourceError> {
+    #[cfg(windows)]
+    if !dev_mode {
+        return Reader::new().map(|reader| Box::new(reader) as Box<dyn SensorSource>);
+    }
+    Ok(Box::new(DevModeSource::new()))
 }
 
-type SharedState = Arc<RwLock<AppState>>;
+type SharedState = Arc<RwLock<Registry>>;
+
+/// Readings are refreshed at most this often, even if HWiNFO reports a
+/// zero or implausibly short polling period.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Rough starting size for the encoded metrics buffer, just to avoid a few
+/// reallocations on the first scrapes; `String` grows past this as needed.
+const METRICS_BODY_CAPACITY: usize = 8 * 1024;
+
+/// Wraps `TcpListener` so every accepted connection has Nagle's algorithm
+/// disabled, trading a few extra small packets for lower scrape latency.
+struct NoDelayListener(tokio::net::TcpListener);
+
+impl Listener for NoDelayListener {
+    type Io = tokio::net::TcpStream;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            match self.0.accept().await {
+                Ok((stream, addr)) => {
+                    if let Err(e) = stream.set_nodelay(true) {
+                        warn!("Failed to set TCP_NODELAY on accepted socket: {}", e);
+                    }
+                    return (stream, addr);
+                },
+                Err(e) => warn!("Failed to accept connection: {}", e),
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.0.local_addr()
+    }
+}
+
+/// A label set usable as a `Family` key for sensor readings. Implemented by
+/// two concrete types rather than making `sensor_id`/`sensor_instance`/
+/// `reading_id` `Option<u32>` fields on a single struct, because
+/// `#[derive(EncodeLabelSet)]` writes one label key per struct field
+/// regardless of whether the value is `Some`/`None` — an `Option` field
+/// would still emit an always-present, always-empty label key. Picking the
+/// concrete type at startup (see `main`) keeps the default exposition
+/// byte-identical to before `stable_ids` existed.
+pub trait SensorLabel: Clone + std::hash::Hash + Eq + EncodeLabelSet + Send + Sync + 'static {
+    fn new(sensor: String, reading: String, unit: String, sensor_id: u32, sensor_instance: u32, reading_id: u32) -> Self;
+}
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
 pub struct HWiNFOLabels {
@@ -46,58 +110,203 @@ pub struct HWiNFOLabels {
     pub unit: String,
 }
 
-#[derive(Default)]
-pub struct Metrics {
-    temperature: Family<HWiNFOLabels, Gauge<f64, AtomicU64>>,
-    voltage: Family<HWiNFOLabels, Gauge<f64, AtomicU64>>,
-    fan_speed: Family<HWiNFOLabels, Gauge<f64, AtomicU64>>,
-    current: Family<HWiNFOLabels, Gauge<f64, AtomicU64>>,
-    power: Family<HWiNFOLabels, Gauge<f64, AtomicU64>>,
-    clock: Family<HWiNFOLabels, Gauge<f64, AtomicU64>>,
-    usage: Family<HWiNFOLabels, Gauge<f64, AtomicU64>>,
-    other: Family<HWiNFOLabels, Gauge<f64, AtomicU64>>,
+impl SensorLabel for HWiNFOLabels {
+    fn new(sensor: String, reading: String, unit: String, _sensor_id: u32, _sensor_instance: u32, _reading_id: u32) -> Self {
+        Self { sensor, reading, unit }
+    }
+}
+
+/// `HWiNFOLabels` plus the numeric identifiers from `HWiNFOSensorElement`/
+/// `HWiNFOReadingElement`, used instead of `HWiNFOLabels` when `stable_ids`
+/// is enabled so dashboards get a join key that survives renaming in HWiNFO.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct HWiNFOLabelsWithIds {
+    pub sensor: String,
+    pub reading: String,
+    pub unit: String,
+    pub sensor_id: u32,
+    pub sensor_instance: u32,
+    pub reading_id: u32,
+}
+
+impl SensorLabel for HWiNFOLabelsWithIds {
+    fn new(sensor: String, reading: String, unit: String, sensor_id: u32, sensor_instance: u32, reading_id: u32) -> Self {
+        Self { sensor, reading, unit, sensor_id, sensor_instance, reading_id }
+    }
+}
+
+/// The min/avg/max series for one reading kind, registered together when
+/// `extra_stats` is enabled.
+struct Stats<L: SensorLabel> {
+    min: Family<L, Gauge<f64, AtomicU64>>,
+    avg: Family<L, Gauge<f64, AtomicU64>>,
+    max: Family<L, Gauge<f64, AtomicU64>>,
 }
 
-impl Metrics {
-    pub fn gauge_reading(&self, label: HWiNFOLabels, reading_type: SensorReadingType, value: f64) {
-        match reading_type {
+impl<L: SensorLabel> Default for Stats<L> {
+    fn default() -> Self {
+        Self { min: Family::default(), avg: Family::default(), max: Family::default() }
+    }
+}
+
+impl<L: SensorLabel> Stats<L> {
+    fn set(&self, label: &L, reading: &HWiNFOReadingElement) {
+        self.min.get_or_create(label).set(reading.min_value);
+        self.avg.get_or_create(label).set(reading.avg_value);
+        self.max.get_or_create(label).set(reading.max_value);
+    }
+
+    fn register(&self, registry: &mut Registry, name: &str, kind: &str) {
+        registry.register(format!("{name}_min"), format!("Minimum observed {kind}"), self.min.clone());
+        registry.register(format!("{name}_avg"), format!("Average observed {kind}"), self.avg.clone());
+        registry.register(format!("{name}_max"), format!("Maximum observed {kind}"), self.max.clone());
+    }
+}
+
+pub struct Metrics<L: SensorLabel> {
+    temperature: Family<L, Gauge<f64, AtomicU64>>,
+    voltage: Family<L, Gauge<f64, AtomicU64>>,
+    fan_speed: Family<L, Gauge<f64, AtomicU64>>,
+    current: Family<L, Gauge<f64, AtomicU64>>,
+    power: Family<L, Gauge<f64, AtomicU64>>,
+    clock: Family<L, Gauge<f64, AtomicU64>>,
+    usage: Family<L, Gauge<f64, AtomicU64>>,
+    other: Family<L, Gauge<f64, AtomicU64>>,
+
+    // Populated only when `extra_stats` is enabled; left unregistered
+    // otherwise so the default cardinality is unchanged.
+    extra_stats: bool,
+    temperature_stats: Stats<L>,
+    voltage_stats: Stats<L>,
+    fan_speed_stats: Stats<L>,
+    current_stats: Stats<L>,
+    power_stats: Stats<L>,
+    clock_stats: Stats<L>,
+    usage_stats: Stats<L>,
+    other_stats: Stats<L>,
+}
+
+// Written by hand instead of `#[derive(Default)]`, which would add a
+// spurious `L: Default` bound even though `Family::default()` doesn't need
+// one.
+impl<L: SensorLabel> Default for Metrics<L> {
+    fn default() -> Self {
+        Self {
+            temperature: Family::default(),
+            voltage: Family::default(),
+            fan_speed: Family::default(),
+            current: Family::default(),
+            power: Family::default(),
+            clock: Family::default(),
+            usage: Family::default(),
+            other: Family::default(),
+            extra_stats: false,
+            temperature_stats: Stats::default(),
+            voltage_stats: Stats::default(),
+            fan_speed_stats: Stats::default(),
+            current_stats: Stats::default(),
+            power_stats: Stats::default(),
+            clock_stats: Stats::default(),
+            usage_stats: Stats::default(),
+            other_stats: Stats::default(),
+        }
+    }
+}
+
+impl<L: SensorLabel> Metrics<L> {
+    pub fn gauge_reading(&self, sensor: &HWiNFOSensorElement, reading: &HWiNFOReadingElement) {
+        let label = L::new(
+            utf8_to_str(&sensor.user_name_utf8),
+            utf8_to_str(&reading.user_label_utf8),
+            utf8_to_str(&reading.unit_utf8),
+            sensor.sensor_id,
+            sensor.sensor_instance,
+            reading.reading_id,
+        );
+        let value = reading.value;
+        match reading.reading_type {
             SensorReadingType::None => {}
-            SensorReadingType::Temp => { self.temperature.get_or_create(&label).set(value); }
-            SensorReadingType::Volt => { self.voltage.get_or_create(&label).set(value); }
-            SensorReadingType::Fan => { self.fan_speed.get_or_create(&label).set(value); }
-            SensorReadingType::Current => { self.current.get_or_create(&label).set(value); }
-            SensorReadingType::Power => { self.power.get_or_create(&label).set(value); }
-            SensorReadingType::Clock => { self.clock.get_or_create(&label).set(value); }
-            SensorReadingType::Usage => { self.usage.get_or_create(&label).set(value); }
-            SensorReadingType::Other => { self.other.get_or_create(&label).set(value); }
+            SensorReadingType::Temp => {
+                self.temperature.get_or_create(&label).set(value);
+                self.gauge_extra_stats(&self.temperature_stats, &label, reading);
+            }
+            SensorReadingType::Volt => {
+                self.voltage.get_or_create(&label).set(value);
+                self.gauge_extra_stats(&self.voltage_stats, &label, reading);
+            }
+            SensorReadingType::Fan => {
+                self.fan_speed.get_or_create(&label).set(value);
+                self.gauge_extra_stats(&self.fan_speed_stats, &label, reading);
+            }
+            SensorReadingType::Current => {
+                self.current.get_or_create(&label).set(value);
+                self.gauge_extra_stats(&self.current_stats, &label, reading);
+            }
+            SensorReadingType::Power => {
+                self.power.get_or_create(&label).set(value);
+                self.gauge_extra_stats(&self.power_stats, &label, reading);
+            }
+            SensorReadingType::Clock => {
+                self.clock.get_or_create(&label).set(value);
+                self.gauge_extra_stats(&self.clock_stats, &label, reading);
+            }
+            SensorReadingType::Usage => {
+                self.usage.get_or_create(&label).set(value);
+                self.gauge_extra_stats(&self.usage_stats, &label, reading);
+            }
+            SensorReadingType::Other => {
+                self.other.get_or_create(&label).set(value);
+                self.gauge_extra_stats(&self.other_stats, &label, reading);
+            }
         };
     }
+
+    fn gauge_extra_stats(&self, stats: &Stats<L>, label: &L, reading: &HWiNFOReadingElement) {
+        if !self.extra_stats {
+            return;
+        }
+        stats.set(label, reading);
+    }
 }
 
 #[tokio::main]
 async fn main() {
-    setup_logger();
-
-    let metrics = Arc::new(RwLock::new(Metrics { ..Default::default() }));
-    let (tx_rq, mut rx_rq) = mpsc::channel::<()>(1);
-    let (tx_rs, rx_rs) = mpsc::channel::<()>(1);
-    let shared_state = Arc::new(RwLock::new(AppState {
-        registry: <Registry>::default(),
-        tx_rq,
-        rx_rs,
-    }));
-
-    let mut state = shared_state.write().await;
+    let dirs = ProjectDirs::from("dev", "Valery Kirichenko", "HWiNFO Prometheus").unwrap();
+    let config = Config::load(&dirs);
+    setup_logger(&dirs, config.log_level);
+
+    if config.stable_ids {
+        run::<HWiNFOLabelsWithIds>(config).await;
+    } else {
+        run::<HWiNFOLabels>(config).await;
+    }
+}
+
+async fn run<L: SensorLabel>(config: Config) {
+    let metrics = Arc::new(RwLock::new(Metrics::<L> { extra_stats: config.extra_stats, ..Default::default() }));
+    let shared_state: SharedState = Arc::new(RwLock::new(Registry::default()));
+
+    let mut registry = shared_state.write().await;
     let metrics_read = metrics.read().await;
-    state.registry.register("temperature", "Temperature measurement", metrics_read.temperature.clone());
-    state.registry.register("voltage", "Voltage measurement", metrics_read.voltage.clone());
-    state.registry.register("fan_speed", "Fan speed measurement", metrics_read.fan_speed.clone());
-    state.registry.register("current", "Current measurement", metrics_read.current.clone());
-    state.registry.register("power", "Power measurement", metrics_read.power.clone());
-    state.registry.register("clock", "Clock speed measurement", metrics_read.clock.clone());
-    state.registry.register("usage", "Usage measurement", metrics_read.usage.clone());
-    state.registry.register("other", "Arbitrary value with its own unit", metrics_read.other.clone());
-    drop(state);
+    registry.register("temperature", "Temperature measurement", metrics_read.temperature.clone());
+    registry.register("voltage", "Voltage measurement", metrics_read.voltage.clone());
+    registry.register("fan_speed", "Fan speed measurement", metrics_read.fan_speed.clone());
+    registry.register("current", "Current measurement", metrics_read.current.clone());
+    registry.register("power", "Power measurement", metrics_read.power.clone());
+    registry.register("clock", "Clock speed measurement", metrics_read.clock.clone());
+    registry.register("usage", "Usage measurement", metrics_read.usage.clone());
+    registry.register("other", "Arbitrary value with its own unit", metrics_read.other.clone());
+    if config.extra_stats {
+        metrics_read.temperature_stats.register(&mut registry, "temperature", "temperature");
+        metrics_read.voltage_stats.register(&mut registry, "voltage", "voltage");
+        metrics_read.fan_speed_stats.register(&mut registry, "fan_speed", "fan speed");
+        metrics_read.current_stats.register(&mut registry, "current", "current");
+        metrics_read.power_stats.register(&mut registry, "power", "power");
+        metrics_read.clock_stats.register(&mut registry, "clock", "clock speed");
+        metrics_read.usage_stats.register(&mut registry, "usage", "usage");
+        metrics_read.other_stats.register(&mut registry, "other", "value");
+    }
+    drop(registry);
     drop(metrics_read);
 
     let app = Router::new()
@@ -105,73 +314,67 @@ async fn main() {
         .layer(Extension(shared_state))
         .layer(Extension(metrics.clone()));
 
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:8080")
+    let listener = tokio::net::TcpListener::bind((config.bind_address.as_str(), config.port))
         .await
         .unwrap();
     info!("Listening on {}", listener.local_addr().unwrap());
 
     let handle = Handle::current();
+    let retry_attempts = config.retry_attempts;
+    let retry_interval = config.retry_interval;
+    let dev_mode = config.dev_mode;
     std::thread::spawn(move || {
-        let mut reader: Reader;
+        let mut source: Box<dyn SensorSource>;
         let mut attempts = 0;
         loop {
-            match Reader::new() {
-                Ok(reader_instance) => {
-                    reader = reader_instance;
-                    info!("HWiNFO Reader is ready");
+            match create_source(dev_mode) {
+                Ok(source_instance) => {
+                    source = source_instance;
+                    info!("Sensor source is ready");
                     break;
                 },
                 Err(_) => {
-                    if attempts > 5 {
+                    if attempts > retry_attempts {
                         error!("HWiNFO is not available, retries exceeded. Exiting...");
                         std::process::exit(1);
                     }
-                    warn!("HWiNFO is not available, retrying in 5s");
+                    warn!("HWiNFO is not available, retrying in {:?}", retry_interval);
                     attempts += 1;
-                    std::thread::sleep(Duration::from_secs(5));
+                    std::thread::sleep(retry_interval);
                 },
             };
         }
 
         loop {
-            handle.block_on(async {
-                rx_rq.recv().await;
-            });
-            reader.update_readings();
-            for reading in &reader.readings {
-                let sensor = reader.sensors.get(reading.sensor_index as usize).unwrap();
-                let reading_type = reading.reading_type;
+            let poll_interval = Duration::from_millis(source.polling_period() as u64).max(MIN_POLL_INTERVAL);
+            std::thread::sleep(poll_interval);
+
+            if source.update().is_err() {
+                error!("Failed to refresh readings, keeping last known values");
+                continue;
+            }
+            for reading in source.readings() {
+                let sensor = source.sensors().get(reading.sensor_index as usize).unwrap();
                 handle.block_on(async {
-                    metrics.read().await.gauge_reading(HWiNFOLabels
-                                                       {
-                                                           sensor: utf8_to_str(&sensor.user_name_utf8),
-                                                           reading: utf8_to_str(&reading.user_label_utf8),
-                                                           unit: utf8_to_str(&reading.unit_utf8),
-                                                       }, reading_type, reading.value);
+                    metrics.read().await.gauge_reading(sensor, reading);
                 });
             }
-            handle.block_on(async {
-                tx_rs.send(()).await.expect("Unable to send a response");
-            });
         }
     });
 
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(NoDelayListener(listener), app).await.unwrap();
 }
 
 async fn handler(Extension(state): Extension<SharedState>) -> String {
-    let mut state = state.write().await;
-    state.tx_rq.send(()).await.expect("Unable to send a request");
-    state.rx_rs.recv().await;
+    let registry = state.read().await;
 
-    let mut body = String::new();
-    encode(&mut body, &state.registry).unwrap();
+    let mut body = String::with_capacity(METRICS_BODY_CAPACITY);
+    encode(&mut body, &registry).unwrap();
 
     body
 }
 
-fn setup_logger() {
-    let dirs = ProjectDirs::from("dev", "Valery Kirichenko", "HWiNFO Prometheus").unwrap();
+fn setup_logger(dirs: &ProjectDirs, log_level: log::LevelFilter) {
     let log_path = dirs.data_local_dir().join("output.log");
     std::fs::create_dir_all(dirs.data_local_dir()).unwrap();
     fern::Dispatch::new()
@@ -183,8 +386,64 @@ fn setup_logger() {
                 message
             ))
         })
-        .level(log::LevelFilter::Debug)
+        .level(log_level)
         .chain(std::io::stdout())
         .chain(fern::log_file(log_path).unwrap())
         .apply().unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dev_mode_source::DevModeSource;
+
+    #[test]
+    fn gauge_reading_routes_value_to_matching_family() {
+        let metrics = Metrics::<HWiNFOLabels>::default();
+        let mut source = DevModeSource::new();
+        source.update().unwrap();
+
+        let sensor = &source.sensors()[0];
+        let temp_reading = source.readings().iter()
+            .find(|reading| matches!(reading.reading_type, SensorReadingType::Temp))
+            .unwrap();
+        metrics.gauge_reading(sensor, temp_reading);
+
+        let label = HWiNFOLabels::new(
+            utf8_to_str(&sensor.user_name_utf8),
+            utf8_to_str(&temp_reading.user_label_utf8),
+            utf8_to_str(&temp_reading.unit_utf8),
+            sensor.sensor_id,
+            sensor.sensor_instance,
+            temp_reading.reading_id,
+        );
+        assert_eq!(metrics.temperature.get_or_create(&label).get(), temp_reading.value);
+        assert_eq!(metrics.voltage.get_or_create(&label).get(), 0.0);
+    }
+
+    #[test]
+    fn gauge_extra_stats_only_populate_when_enabled() {
+        let mut source = DevModeSource::new();
+        source.update().unwrap();
+        let sensor = &source.sensors()[0];
+        let temp_reading = source.readings().iter()
+            .find(|reading| matches!(reading.reading_type, SensorReadingType::Temp))
+            .unwrap();
+        let label = HWiNFOLabels::new(
+            utf8_to_str(&sensor.user_name_utf8),
+            utf8_to_str(&temp_reading.user_label_utf8),
+            utf8_to_str(&temp_reading.unit_utf8),
+            sensor.sensor_id,
+            sensor.sensor_instance,
+            temp_reading.reading_id,
+        );
+
+        let metrics = Metrics::<HWiNFOLabels>::default();
+        metrics.gauge_reading(sensor, temp_reading);
+        assert_eq!(metrics.temperature_stats.max.get_or_create(&label).get(), 0.0);
+
+        let metrics_with_stats = Metrics::<HWiNFOLabels> { extra_stats: true, ..Default::default() };
+        metrics_with_stats.gauge_reading(sensor, temp_reading);
+        assert_eq!(metrics_with_stats.temperature_stats.max.get_or_create(&label).get(), temp_reading.max_value);
+    }
+}
@@ -0,0 +1,25 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+use crate::hwinfo_types::{HWiNFOReadingElement, HWiNFOSensorElement};
+
+/// A source of HWiNFO-shaped sensor data. Implemented by `Reader` for the
+/// real shared-memory segment on Windows, and by `DevModeSource` for
+/// exercising the rest of the pipeline without HWiNFO or Windows.
+pub trait SensorSource {
+    fn sensors(&self) -> &[HWiNFOSensorElement];
+    fn readings(&self) -> &[HWiNFOReadingElement];
+    fn polling_period(&self) -> u32;
+    fn update(&mut self) -> Result<(), SourceError>;
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceError;
+
+impl Display for SourceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Can't read shared memory. Is HWiNFO running and shared memory support is enabled?")
+    }
+}
+
+impl Error for SourceError {}